@@ -1,19 +1,183 @@
+extern crate clap;
 extern crate sudoku_ai;
 
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use sudoku_ai::{Difficulty, Hint, Puzzle};
+
+#[derive(Parser)]
+#[command(name = "sudoku_ai", about = "Solve, rate, and generate sudoku puzzles")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve a puzzle read from a file, or passed inline with --string
+    Solve {
+        file: Option<PathBuf>,
+        #[arg(long)]
+        string: Option<String>,
+    },
+    /// Generate a new, uniquely-solvable puzzle
+    Generate {
+        #[arg(long, value_enum, default_value = "medium")]
+        difficulty: DifficultyArg,
+    },
+    /// Rate how hard a puzzle is to solve by hand
+    Rate {
+        file: PathBuf,
+    },
+    /// Step through a puzzle interactively: inspect domains, set/unset
+    /// squares, and ask for the next logical deduction
+    Repl {
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum DifficultyArg {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl From<DifficultyArg> for Difficulty {
+    fn from(value: DifficultyArg) -> Difficulty {
+        match value {
+            DifficultyArg::Easy => Difficulty::Easy,
+            DifficultyArg::Medium => Difficulty::Medium,
+            DifficultyArg::Hard => Difficulty::Hard,
+            DifficultyArg::Expert => Difficulty::Expert,
+        }
+    }
+}
+
 fn main() {
-    let string = "\
-        0 4 0 0 6 0 1 2 5 \
-        2 6 0 0 4 7 0 0 0 \
-        0 0 8 5 3 0 0 0 7 \
-        6 0 0 0 5 1 7 3 0 \
-        0 7 1 0 0 8 9 0 0 \
-        9 0 2 6 0 4 0 0 8 \
-        0 5 9 2 0 0 0 0 0 \
-        3 1 0 0 8 5 0 0 4 \
-        8 0 7 0 9 0 6 0 1";
-    let mut puzzle = sudoku_ai::Puzzle::read_from_string(string);
-    if let Err(message) = puzzle.solve() {
-        println!("{}", message);
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Solve { file, string }) => solve(file, string),
+        Some(Command::Generate { difficulty }) => generate(difficulty.into()),
+        Some(Command::Rate { file }) => rate(file),
+        Some(Command::Repl { file }) => repl(file),
+        None => repl(None),
+    }
+}
+
+fn load_puzzle(file: Option<PathBuf>, string: Option<String>) -> Puzzle {
+    let result = match (file, string) {
+        (Some(path), None) => {
+            let mut handle = File::open(path).expect("could not open puzzle file");
+            Puzzle::read_from_file(&mut handle)
+        },
+        (None, Some(source)) => Puzzle::read_from_string(&source),
+        _ => {
+            eprintln!("pass exactly one of a file path or --string");
+            process::exit(1);
+        },
     };
+    match result {
+        Ok(puzzle) => puzzle,
+        Err(error) => {
+            eprintln!("could not parse puzzle: {}", error);
+            process::exit(1);
+        },
+    }
+}
+
+fn solve(file: Option<PathBuf>, string: Option<String>) {
+    let mut puzzle = load_puzzle(file, string);
+    match puzzle.solve() {
+        Ok(()) => println!("{}", puzzle),
+        Err(message) => println!("{}", message),
+    }
+}
+
+fn rate(file: PathBuf) {
+    let mut puzzle = load_puzzle(Some(file), None);
+    match puzzle.solve_logic() {
+        Ok(difficulty) => println!("{:?}", difficulty),
+        Err(message) => println!("{}", message),
+    }
+}
+
+fn generate(difficulty: Difficulty) {
+    let puzzle = Puzzle::generate(difficulty);
     println!("{}", puzzle);
 }
+
+fn repl(file: Option<PathBuf>) {
+    let mut puzzle = match file {
+        Some(path) => load_puzzle(Some(path), None),
+        None => {
+            println!("Paste a grid (compact or whitespace-separated), then press Enter:");
+            let mut input = String::new();
+            io::stdin().read_line(&mut input).expect("failed to read grid");
+            match Puzzle::read_from_string(&input) {
+                Ok(puzzle) => puzzle,
+                Err(error) => {
+                    eprintln!("could not parse puzzle: {}", error);
+                    process::exit(1);
+                },
+            }
+        },
+    };
+
+    println!("Commands: show, domains, hint, set <index> <value>, unset <index>, quit");
+    loop {
+        print!("> ");
+        io::stdout().flush().ok();
+
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("show") | None => println!("{}", puzzle),
+            Some("domains") => {
+                puzzle.refresh_domains();
+                for index in 0..puzzle.dimension() * puzzle.dimension() {
+                    if puzzle.get_value(index) == 0 {
+                        println!("{}: {:?}", index, puzzle.domain_at(index));
+                    }
+                }
+            },
+            Some("hint") => match puzzle.next_hint() {
+                Some(Hint::Assign{index, value, technique}) =>
+                    println!("{} ({}) -> square {}", technique, value, index),
+                Some(Hint::Eliminate{technique}) =>
+                    println!("{} eliminated some candidates", technique),
+                None => println!("no further logical deduction found"),
+            },
+            Some("set") => {
+                let index = words.next().and_then(|word| word.parse().ok());
+                let value = words.next().and_then(|word| word.parse().ok());
+                match (index, value) {
+                    (Some(index), Some(value))
+                        if index < puzzle.dimension() * puzzle.dimension()
+                            && value <= puzzle.dimension() =>
+                        puzzle.set_value(index, value),
+                    (Some(_), Some(_)) => println!("index/value out of range"),
+                    _ => println!("usage: set <index> <value>"),
+                }
+            },
+            Some("unset") => match words.next().and_then(|word| word.parse().ok()) {
+                Some(index) if index < puzzle.dimension() * puzzle.dimension() =>
+                    puzzle.set_value(index, 0),
+                Some(_) => println!("index out of range"),
+                None => println!("usage: unset <index>"),
+            },
+            Some("quit") | Some("exit") => break,
+            Some(other) => println!("unknown command: {}", other),
+        }
+    }
+}
@@ -1,8 +1,11 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
 use std::fs::File;
 use std::io::prelude::*;
+use std::time::{Duration, Instant};
 
+use super::random::Rng;
 use super::square::Square;
 
 
@@ -13,34 +16,24 @@ use super::square::Square;
 /// # extern crate sudoku_ai;
 /// # use sudoku_ai::Puzzle;
 /// # fn main() {
-/// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1");
+/// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
 ///
 /// assert!(puzzle.is_valid());
 /// # }
 /// ```
+#[derive(Clone)]
 pub struct Puzzle {
     dimension: usize,
+    box_width: usize,
+    box_height: usize,
     squares: Vec<Square>,
 }
 
 impl Puzzle {
     /// Read a sudoku puzzle from a file.
     ///
-    /// The format of the file is expected to be series of integers separated
-    /// by whitespace, though any whitespace will do. So for a 4x4 puzzle,
-    /// you could have it all in one line:
-    /// ```text
-    /// // puzzle.txt
-    /// 1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1
-    /// ```
-    /// ... or for more readability, split it into rows:
-    /// ```text
-    /// // puzzle.txt
-    /// 1 2 3 4
-    /// 3 4 1 2
-    /// 2 1 4 3
-    /// 4 3 2 1
-    /// ```
+    /// Accepts the same formats as `read_from_string`; see there for
+    /// details.
     ///
     /// # Example
     /// ```
@@ -51,55 +44,137 @@ impl Puzzle {
     /// # use std::io::prelude::*;
     /// # fn foo () -> std::io::Result<()> {
     /// let mut file = File::open("puzzle.txt")?;
-    /// let mut puzzle = Puzzle::read_from_file(&mut file);
+    /// let puzzle = Puzzle::read_from_file(&mut file).unwrap();
     ///
     /// assert!(puzzle.is_valid());
     /// # Ok(())
     /// # }
     /// # }
     /// ```
-    pub fn read_from_file(source: &mut File) -> Puzzle {
+    pub fn read_from_file(source: &mut File) -> Result<Puzzle, ParseError> {
         let mut contents = String::new();
         source.read_to_string(&mut contents).unwrap();
-        let squares = contents.split_whitespace()
-                              .filter_map(|x| x.parse::<usize>().ok())
-                              .map(|x| Square::new(x))
-                              .collect::<Vec<_>>();
-        let size = squares.len();
-        let dimension = (size as f64).sqrt() as usize;
-        let mut p = Puzzle {
-            dimension,
-            squares,
-        };
-        p.reset_domains();
-        p
+        Puzzle::parse(&contents)
     }
 
     /// Read a sudoku puzzle from a string.
     ///
-    /// The format of the string is expected to be series of integers separated
-    /// by whitespace, though any whitespace will do.
+    /// Two formats are accepted:
+    /// - Compact: one character per cell with no separators, e.g.
+    ///   `53..7....6..195...` for a (partial) 9x9 grid. `.`, `_`, and `0`
+    ///   all mean an empty cell. Only usable for orders up to 9, since a
+    ///   cell value needs to fit in a single digit.
+    /// - Token: cell values (`0` for empty) separated by any whitespace,
+    ///   including newlines, tabs, and CRLF line endings. This is the only
+    ///   format that can express orders of 10 or higher, where a cell
+    ///   value needs more than one digit.
+    ///
+    /// Which format is in use is detected from the *unstripped* source: if
+    /// it contains no whitespace other than line breaks between rows, and
+    /// what's left is made up entirely of digits/`.`/`_`, it's read as
+    /// compact; otherwise it's read as whitespace-separated tokens. Judging
+    /// this from the source stripped of all whitespace would conflate the
+    /// two formats, since a token grid's digits are also "entirely
+    /// digits/`.`/`_`" once its separating whitespace is removed.
+    ///
+    /// The box shape is assumed to be square, which only works for orders
+    /// whose dimension is itself a perfect square (4, 9, 16, ...). Puzzles
+    /// with rectangular boxes (6x6, 12x12, ...) should use `with_box_shape`.
     ///
     /// # Example
     /// ```
     /// # extern crate sudoku_ai;
     /// # use sudoku_ai::Puzzle;
     /// # fn main() {
-    /// let puzzle_string = String::from("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1");
-    /// let mut puzzle = Puzzle::read_from_string(&puzzle_string);
-    ///
+    /// let puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// assert!(puzzle.is_valid());
+    ///
+    /// let compact = Puzzle::read_from_string("123.\n3412\n2143\n4321").unwrap();
+    /// assert!(compact.is_valid());
+    ///
+    /// // Order 16 needs two-digit tokens (10-16), so only the
+    /// // whitespace-separated format can express it.
+    /// let wide = Puzzle::read_from_string(
+    ///     "1 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n\
+    ///      0 2 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n\
+    ///      0 0 3 0 0 0 0 0 0 0 0 0 0 0 0 0\n\
+    ///      0 0 0 4 0 0 0 0 0 0 0 0 0 0 0 0\n\
+    ///      0 0 0 0 5 0 0 0 0 0 0 0 0 0 0 0\n\
+    ///      0 0 0 0 0 6 0 0 0 0 0 0 0 0 0 0\n\
+    ///      0 0 0 0 0 0 7 0 0 0 0 0 0 0 0 0\n\
+    ///      0 0 0 0 0 0 0 8 0 0 0 0 0 0 0 0\n\
+    ///      0 0 0 0 0 0 0 0 9 0 0 0 0 0 0 0\n\
+    ///      0 0 0 0 0 0 0 0 0 10 0 0 0 0 0 0\n\
+    ///      0 0 0 0 0 0 0 0 0 0 11 0 0 0 0 0\n\
+    ///      0 0 0 0 0 0 0 0 0 0 0 12 0 0 0 0\n\
+    ///      0 0 0 0 0 0 0 0 0 0 0 0 13 0 0 0\n\
+    ///      0 0 0 0 0 0 0 0 0 0 0 0 0 14 0 0\n\
+    ///      0 0 0 0 0 0 0 0 0 0 0 0 0 0 15 0\n\
+    ///      0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 16"
+    /// ).unwrap();
+    /// assert_eq!(wide.dimension(), 16);
+    /// assert_eq!(wide.get_value(153), 10);
+    /// assert!(wide.is_valid());
     /// # }
     /// ```
-    pub fn read_from_string(source: &str) -> Puzzle {
-        let squares = source.split(" ")
-                            .filter_map(|x| x.parse::<usize>().ok())
-                            .map(|x| Square::new(x))
-                            .collect::<Vec<_>>();
-        let size = squares.len();
-        let dimension = (size as f64).sqrt() as usize;
+    pub fn read_from_string(source: &str) -> Result<Puzzle, ParseError> {
+        Puzzle::parse(source)
+    }
+
+    /// The shared parsing core behind `read_from_string`/`read_from_file`.
+    fn parse(source: &str) -> Result<Puzzle, ParseError> {
+        let values = parse_cells(source)?;
+        let squares = values.into_iter().map(Square::new).collect::<Vec<_>>();
+        Ok(Puzzle::from_squares(squares))
+    }
+
+    /// Builds a puzzle with an explicit box shape, for orders whose boxes
+    /// aren't square, such as 6x6 (2x3 boxes) or 12x12 (3x4 boxes).
+    /// `values` is a flat, row-major list of cell values (`0` for empty),
+    /// and its length must be a perfect square.
+    ///
+    /// # Example
+    /// ```
+    /// # extern crate sudoku_ai;
+    /// # use sudoku_ai::Puzzle;
+    /// # fn main() {
+    /// // A 6x6 grid made of 2-row, 3-column boxes.
+    /// let values = vec![
+    ///     1, 2, 3, 4, 5, 6,
+    ///     4, 5, 6, 1, 2, 3,
+    ///     2, 3, 1, 5, 6, 4,
+    ///     5, 6, 4, 2, 3, 1,
+    ///     3, 1, 2, 6, 4, 5,
+    ///     6, 4, 5, 3, 1, 2,
+    /// ];
+    /// let puzzle = Puzzle::with_box_shape(&values, 3, 2);
+    ///
+    /// assert!(puzzle.is_solved());
+    /// # }
+    /// ```
+    pub fn with_box_shape(values: &[usize], box_width: usize, box_height: usize) -> Puzzle {
+        let squares = values.iter().map(|&value| Square::new(value)).collect::<Vec<_>>();
+        let dimension = (squares.len() as f64).sqrt() as usize;
         let mut p = Puzzle {
             dimension,
+            box_width,
+            box_height,
+            squares,
+        };
+        p.reset_domains();
+        p
+    }
+
+    /// Shared by `read_from_file`/`read_from_string`: infers a square box
+    /// shape from the dimension, which is correct for every perfect-square
+    /// order this crate originally supported.
+    fn from_squares(squares: Vec<Square>) -> Puzzle {
+        let dimension = (squares.len() as f64).sqrt() as usize;
+        let box_size = (dimension as f64).sqrt() as usize;
+        let mut p = Puzzle {
+            dimension,
+            box_width: box_size,
+            box_height: box_size,
             squares,
         };
         p.reset_domains();
@@ -116,11 +191,11 @@ impl Puzzle {
     /// # extern crate sudoku_ai;
     /// # use sudoku_ai::Puzzle;
     /// # fn main() {
-    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1");
+    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// assert!(puzzle.is_solved());
     ///
     /// // We replace a filled square with a zero to make the puzzle unsolved
-    /// puzzle = Puzzle::read_from_string("1 2 3 4 0 4 1 2 2 1 4 3 4 3 2 1");
+    /// puzzle = Puzzle::read_from_string("1 2 3 4 0 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// assert!(!puzzle.is_solved());
     /// # }
     /// ```
@@ -132,6 +207,40 @@ impl Puzzle {
         self.squares.iter().all(|square| square.value != 0)
     }
 
+    /// The puzzle's order, i.e. the number of rows/columns/values. A
+    /// standard sudoku has a dimension of 9.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// The value at `index` in the flat, row-major list of squares, or `0`
+    /// if it's empty.
+    pub fn get_value(&self, index: usize) -> usize {
+        self.squares[index].value
+    }
+
+    /// Sets the value at `index` directly, bypassing domain tracking. Pass
+    /// `0` to clear a square back to empty. Used by front ends that let a
+    /// user edit a square, such as the REPL.
+    pub fn set_value(&mut self, index: usize, value: usize) {
+        self.squares[index].value = value;
+    }
+
+    /// Recomputes every square's domain from the current values. Front
+    /// ends should call this after editing squares directly and before
+    /// inspecting domains with `domain_at`.
+    pub fn refresh_domains(&mut self) {
+        self.reset_domains();
+        self.update_domains();
+    }
+
+    /// The candidates still left in `index`'s domain, sorted ascending.
+    pub fn domain_at(&self, index: usize) -> Vec<usize> {
+        let mut values: Vec<usize> = self.squares[index].domain().iter().cloned().collect();
+        values.sort();
+        values
+    }
+
     /// Returns a bool based on whether the puzzle is valid.
     ///
     /// A puzzle can be valid without being solved yet. A valid puzzle requires
@@ -145,15 +254,15 @@ impl Puzzle {
     /// # extern crate sudoku_ai;
     /// # use sudoku_ai::Puzzle;
     /// # fn main() {
-    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1");
+    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// assert!(puzzle.is_valid());
     ///
     /// // Even an unsolved puzzle can be valid
-    /// puzzle = Puzzle::read_from_string("1 2 3 4 0 4 1 2 2 1 4 3 4 3 2 1");
+    /// puzzle = Puzzle::read_from_string("1 2 3 4 0 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// assert!(puzzle.is_valid());
     ///
     /// // But not if we have a duplicate in the first row
-    /// puzzle = Puzzle::read_from_string("1 2 4 4 0 4 1 2 2 1 4 3 4 3 2 1");
+    /// puzzle = Puzzle::read_from_string("1 2 4 4 0 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// assert!(!puzzle.is_valid());
     /// # }
     /// ```
@@ -176,9 +285,9 @@ impl Puzzle {
             }
             set.clear();
 
-            let group_dimension = (self.dimension as f64).sqrt() as usize;
-            let x = (counter % group_dimension) * group_dimension;
-            let y = (counter / group_dimension) * group_dimension;
+            let boxes_per_row = self.dimension / self.box_width;
+            let x = (counter % boxes_per_row) * self.box_width;
+            let y = (counter / boxes_per_row) * self.box_height;
             for value in self.get_group(x, y) {
                 if !set.insert(value) {
                     return false;
@@ -235,17 +344,10 @@ impl Puzzle {
     }
 
     fn get_group(&self, x: usize, y: usize) -> Vec<usize> {
-        let group_dimension = (self.dimension as f64).sqrt() as usize;
-        let group_initial_x = (x / group_dimension) * group_dimension;
-        let group_initial_y = (y / group_dimension) * group_dimension;
-        let initial = group_initial_x + group_initial_y * self.dimension;
-        let mut result = Vec::new();
-        for counter in 0..group_dimension {
-            let initial_skip = initial + (counter * group_dimension.pow(2));
-            result.extend(self.squares.iter().skip(initial_skip).take(group_dimension));
-        }
-        result.iter().filter(|square| square.value != 0)
-              .map(|square| square.value).collect()
+        self.group_indices(x, y).iter()
+            .map(|&index| &self.squares[index])
+            .filter(|square| square.value != 0)
+            .map(|square| square.value).collect()
     }
 
     fn find_next_n_domain(&self, n: usize) -> Option<usize> {
@@ -264,21 +366,27 @@ impl Puzzle {
     /// # extern crate sudoku_ai;
     /// # use sudoku_ai::Puzzle;
     /// # fn main() {
-    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1");
+    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// // Already solved puzzle will be solved quickly
     /// assert_eq!(puzzle.solve(), Ok(()));
     ///
-    /// puzzle = Puzzle::read_from_string("1 2 0 0 3 4 1 2 2 1 4 3 4 3 2 1");
+    /// puzzle = Puzzle::read_from_string("1 2 0 0 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// // Almost solved puzzle will also be solved quickly
     /// assert_eq!(puzzle.solve(), Ok(()));
     ///
-    /// puzzle = Puzzle::read_from_string("1 2 3 3 0 4 1 2 2 1 4 3 4 3 2 1");
+    /// puzzle = Puzzle::read_from_string("1 2 3 3 0 4 1 2 2 1 4 3 4 3 2 1").unwrap();
     /// // A puzzle with an error in it will return an Err<&str>
     /// assert_eq!(puzzle.solve(), Err("The sudoku puzzle is invalid"));
     /// # }
     /// ```
     pub fn solve(&mut self) -> Result<(), &str> {
         self.reset_domains();
+        self.backtrack()
+    }
+
+    /// Runs the clone-and-guess search used by `solve`, starting from
+    /// whatever domains are currently populated.
+    fn backtrack(&mut self) -> Result<(), &str> {
         let mut history: Vec<Snapshot> = Vec::new();
         loop {
             self.update_domains();
@@ -291,6 +399,90 @@ impl Puzzle {
                     },
                     None => return Err("The sudoku puzzle is invalid"),
                 }
+                continue;
+            }
+            match self.find_next_n_domain(1) {
+                Some(index) => {
+                    self.squares[index].set_value_from_domain();
+                },
+                None => {
+                    match self.find_next_n_domain(2).or_else(|| self.find_next_empty_square()) {
+                        Some(index) => {
+                            let squares = self.squares.clone();
+                            history.push(Snapshot{squares, index});
+                            self.squares[index].guess();
+                        },
+                        None => return Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Counts how many distinct solutions the puzzle has, stopping early
+    /// once `cap` have been found.
+    ///
+    /// This reuses the same clone-and-guess search as `solve`, but instead
+    /// of stopping at the first complete assignment, it records it and
+    /// then undoes the last guess to keep exploring the rest of the search
+    /// tree. A `cap` of `2` is enough to prove a puzzle is uniquely
+    /// solvable without enumerating every solution.
+    ///
+    /// This is a read-only query: the search fills in domains and guesses
+    /// as it goes, but the puzzle's squares are restored to their original
+    /// values before returning, regardless of how many solutions were
+    /// found.
+    ///
+    /// # Example
+    /// ```
+    /// # extern crate sudoku_ai;
+    /// # use sudoku_ai::Puzzle;
+    /// # fn main() {
+    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
+    /// assert_eq!(puzzle.count_solutions(2), 1);
+    /// # }
+    /// ```
+    pub fn count_solutions(&mut self, cap: usize) -> usize {
+        if cap == 0 {
+            return 0;
+        }
+        let original = self.squares.clone();
+        let count = self.count_solutions_search(cap);
+        self.squares = original;
+        count
+    }
+
+    fn count_solutions_search(&mut self, cap: usize) -> usize {
+        self.reset_domains();
+        let mut history: Vec<Snapshot> = Vec::new();
+        let mut count = 0;
+        loop {
+            self.update_domains();
+            if !self.is_valid() {
+                match history.pop() {
+                    Some(Snapshot{squares, index}) => {
+                        let wrong_value = self.squares[index].value;
+                        self.squares = squares;
+                        self.squares[index].remove_from_domain(&wrong_value);
+                    },
+                    None => return count,
+                }
+                continue;
+            }
+            if self.all_filled() {
+                count += 1;
+                if count >= cap {
+                    return count;
+                }
+                match history.pop() {
+                    Some(Snapshot{squares, index}) => {
+                        let found_value = self.squares[index].value;
+                        self.squares = squares;
+                        self.squares[index].remove_from_domain(&found_value);
+                    },
+                    None => return count,
+                }
+                continue;
             }
             match self.find_next_n_domain(1) {
                 Some(index) => {
@@ -301,15 +493,554 @@ impl Puzzle {
                         Some(index) => {
                             let squares = self.squares.clone();
                             history.push(Snapshot{squares, index});
-                            self.squares[index].set_value_from_domain();
+                            self.squares[index].guess();
                         },
-                        None => break
+                        None => return count,
                     }
                 }
             }
         }
-        Ok(())
     }
+
+    /// Solves the puzzle using only deduction on `Square::domain` sets,
+    /// escalating through a ladder of techniques, and only guessing as a
+    /// last resort if the ladder stalls before the puzzle is complete.
+    ///
+    /// Returns the hardest technique that was needed as a `Difficulty`, so
+    /// callers can classify a puzzle as easy/medium/hard/expert. Still
+    /// returns `Err` if the puzzle is invalid, same as `solve`.
+    ///
+    /// # Example
+    /// ```
+    /// # extern crate sudoku_ai;
+    /// # use sudoku_ai::Puzzle;
+    /// # fn main() {
+    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
+    /// assert_eq!(puzzle.solve_logic(), Ok(sudoku_ai::Difficulty::Easy));
+    /// # }
+    /// ```
+    pub fn solve_logic(&mut self) -> Result<Difficulty, &str> {
+        self.reset_domains();
+        let mut difficulty = Difficulty::Easy;
+        loop {
+            self.update_domains();
+            if !self.is_valid() {
+                return Err("The sudoku puzzle is invalid");
+            }
+            if self.all_filled() {
+                return Ok(difficulty);
+            }
+            if let Some(index) = self.find_next_n_domain(1) {
+                self.squares[index].set_value_from_domain();
+                continue;
+            }
+            if let Some((index, value)) = self.find_hidden_single() {
+                self.squares[index].assign(value);
+                difficulty = difficulty.max(Difficulty::Medium);
+                continue;
+            }
+            if self.eliminate_naked_subsets() {
+                difficulty = difficulty.max(Difficulty::Hard);
+                continue;
+            }
+            if self.eliminate_pointing_pairs() {
+                difficulty = difficulty.max(Difficulty::Expert);
+                continue;
+            }
+            return self.backtrack().map(|()| difficulty);
+        }
+    }
+
+    /// Applies a single step of the `solve_logic` technique ladder and
+    /// reports what it did, instead of looping to completion. This is what
+    /// lets the REPL walk a user through a puzzle one deduction at a time.
+    /// Returns `None` once the ladder has nothing left to do.
+    pub fn next_hint(&mut self) -> Option<Hint> {
+        self.update_domains();
+        if let Some(index) = self.find_next_n_domain(1) {
+            let value = *self.squares[index].domain().iter().next()?;
+            self.squares[index].set_value_from_domain();
+            return Some(Hint::Assign { index, value, technique: "naked single" });
+        }
+        if let Some((index, value)) = self.find_hidden_single() {
+            self.squares[index].assign(value);
+            return Some(Hint::Assign { index, value, technique: "hidden single" });
+        }
+        if self.eliminate_naked_subsets() {
+            return Some(Hint::Eliminate { technique: "naked pair/triple" });
+        }
+        if self.eliminate_pointing_pairs() {
+            return Some(Hint::Eliminate { technique: "pointing pair" });
+        }
+        None
+    }
+
+    /// Generates a random, uniquely-solvable puzzle at least as hard as
+    /// `target`: a complete grid is filled in with `solve_annealing`, then
+    /// squares are removed in random order as long as `count_solutions`
+    /// still proves the puzzle has exactly one solution.
+    ///
+    /// Only produces standard 9x9 grids. Gives up chasing the requested
+    /// difficulty after a bounded number of attempts and returns the
+    /// hardest uniquely-solvable candidate found instead.
+    pub fn generate(target: Difficulty) -> Puzzle {
+        let mut fallback: Option<(Difficulty, Puzzle)> = None;
+        for _ in 0..50 {
+            let mut puzzle = Puzzle::with_box_shape(&vec![0; 81], 3, 3);
+            let schedule = AnnealingSchedule::new(2.0, 0.999, 2.0, 400);
+            let budget = AnnealingBudget::new(200_000, Duration::from_secs(5));
+            if puzzle.solve_annealing(schedule, budget).is_err() {
+                continue;
+            }
+
+            let mut rng = Rng::new();
+            let mut order: Vec<usize> = (0..puzzle.squares.len()).collect();
+            rng.shuffle(&mut order);
+            for index in order {
+                let removed = puzzle.squares[index].value;
+                puzzle.squares[index].value = 0;
+                if puzzle.count_solutions(2) != 1 {
+                    puzzle.squares[index].value = removed;
+                }
+            }
+
+            match puzzle.clone().solve_logic() {
+                Ok(difficulty) if difficulty >= target => return puzzle,
+                Ok(difficulty) => {
+                    if fallback.as_ref().map_or(true, |&(best, _)| difficulty > best) {
+                        fallback = Some((difficulty, puzzle));
+                    }
+                },
+                Err(_) => {},
+            }
+        }
+        fallback.expect("at least one attempt should have produced a uniquely-solvable grid").1
+    }
+
+    /// Scans every row, column, and box for a candidate that only fits in
+    /// one of that unit's empty squares, and returns the square's index
+    /// and the value to assign there.
+    fn find_hidden_single(&self) -> Option<(usize, usize)> {
+        for unit in self.all_units() {
+            for value in 1..=self.dimension {
+                let mut candidates = unit.iter().cloned()
+                    .filter(|&index| self.squares[index].value == 0
+                                      && self.squares[index].domain().contains(&value));
+                if let Some(index) = candidates.next() {
+                    if candidates.next().is_none() {
+                        return Some((index, value));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Finds naked pairs and triples: N squares in a unit whose domains
+    /// only contain the same N candidates between them. Those candidates
+    /// can be removed from every other square's domain in the unit.
+    /// Returns whether any domain was changed.
+    fn eliminate_naked_subsets(&mut self) -> bool {
+        let mut changed = false;
+        for size in 2..=3 {
+            for unit in self.all_units() {
+                changed |= self.eliminate_naked_subset_in_unit(&unit, size);
+            }
+        }
+        changed
+    }
+
+    fn eliminate_naked_subset_in_unit(&mut self, unit: &[usize], size: usize) -> bool {
+        let mut changed = false;
+        let candidates: Vec<usize> = unit.iter().cloned()
+            .filter(|&index| self.squares[index].value == 0
+                              && self.squares[index].get_domain_size() > 0
+                              && self.squares[index].get_domain_size() <= size)
+            .collect();
+        for combo in combinations(&candidates, size) {
+            let union: HashSet<usize> = combo.iter()
+                .flat_map(|&index| self.squares[index].domain().iter().cloned())
+                .collect();
+            if union.len() != size {
+                continue;
+            }
+            for &index in unit {
+                if combo.contains(&index) {
+                    continue;
+                }
+                for &value in &union {
+                    changed |= self.squares[index].remove_from_domain(&value);
+                }
+            }
+        }
+        changed
+    }
+
+    /// Pointing pair / box-line reduction: if every square in a box that
+    /// can hold a candidate lies in a single row or column, that candidate
+    /// can be removed from the rest of the row/column outside the box.
+    /// Returns whether any domain was changed.
+    fn eliminate_pointing_pairs(&mut self) -> bool {
+        let mut changed = false;
+        for group_y in 0..self.dimension / self.box_height {
+            for group_x in 0..self.dimension / self.box_width {
+                let box_indices = self.group_indices(group_x * self.box_width, group_y * self.box_height);
+                for value in 1..=self.dimension {
+                    let cells: Vec<usize> = box_indices.iter().cloned()
+                        .filter(|&index| self.squares[index].value == 0
+                                          && self.squares[index].domain().contains(&value))
+                        .collect();
+                    if cells.len() < 2 {
+                        continue;
+                    }
+                    let rows: HashSet<usize> = cells.iter().map(|&index| index / self.dimension).collect();
+                    if rows.len() == 1 {
+                        let row = *rows.iter().next().unwrap();
+                        for index in self.row_indices(row) {
+                            if !box_indices.contains(&index) {
+                                changed |= self.squares[index].remove_from_domain(&value);
+                            }
+                        }
+                    }
+                    let columns: HashSet<usize> = cells.iter().map(|&index| index % self.dimension).collect();
+                    if columns.len() == 1 {
+                        let column = *columns.iter().next().unwrap();
+                        for index in self.column_indices(column) {
+                            if !box_indices.contains(&index) {
+                                changed |= self.squares[index].remove_from_domain(&value);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    fn row_indices(&self, y: usize) -> Vec<usize> {
+        let start = y * self.dimension;
+        (start..start + self.dimension).collect()
+    }
+
+    fn column_indices(&self, x: usize) -> Vec<usize> {
+        (0..self.dimension).map(|y| y * self.dimension + x).collect()
+    }
+
+    fn group_indices(&self, x: usize, y: usize) -> Vec<usize> {
+        let group_initial_x = (x / self.box_width) * self.box_width;
+        let group_initial_y = (y / self.box_height) * self.box_height;
+        let mut result = Vec::with_capacity(self.dimension);
+        for row in 0..self.box_height {
+            for column in 0..self.box_width {
+                result.push((group_initial_y + row) * self.dimension + group_initial_x + column);
+            }
+        }
+        result
+    }
+
+    /// Solves the puzzle with simulated annealing instead of propagation
+    /// and backtracking.
+    ///
+    /// Each box is first filled with a random permutation of
+    /// `1..=dimension`, leaving the given clue squares fixed, so every box
+    /// is always internally valid. Energy is the number of duplicate
+    /// values left across all rows and columns. A neighbor move swaps two
+    /// non-fixed squares within the same box; a move that raises energy is
+    /// still accepted with probability `exp(-delta / temperature)`, and
+    /// `schedule` controls how temperature cools and reheats when the
+    /// search stalls. Stops as soon as energy reaches zero or `budget` is
+    /// exhausted.
+    ///
+    /// # Example
+    /// ```
+    /// # extern crate sudoku_ai;
+    /// # use sudoku_ai::{Puzzle, AnnealingSchedule, AnnealingBudget};
+    /// # use std::time::Duration;
+    /// # fn main() {
+    /// let mut puzzle = Puzzle::read_from_string("1 2 3 4 3 4 1 2 2 1 4 3 4 3 2 1").unwrap();
+    /// let schedule = AnnealingSchedule::new(1.0, 0.995, 1.0, 200);
+    /// let budget = AnnealingBudget::new(10_000, Duration::from_millis(500));
+    /// assert_eq!(puzzle.solve_annealing(schedule, budget), Ok(()));
+    /// # }
+    /// ```
+    pub fn solve_annealing(&mut self, schedule: AnnealingSchedule, budget: AnnealingBudget) -> Result<(), &str> {
+        if !self.is_valid() {
+            return Err("The sudoku puzzle is invalid");
+        }
+        let boxes_per_row = self.dimension / self.box_width;
+        let boxes_per_column = self.dimension / self.box_height;
+        let fixed: Vec<bool> = self.squares.iter().map(|square| square.value != 0).collect();
+        let mut rng = Rng::new();
+
+        for group_y in 0..boxes_per_column {
+            for group_x in 0..boxes_per_row {
+                let box_indices = self.group_indices(group_x * self.box_width, group_y * self.box_height);
+                let used: HashSet<usize> = box_indices.iter()
+                    .map(|&index| self.squares[index].value)
+                    .filter(|&value| value != 0)
+                    .collect();
+                let mut missing: Vec<usize> = (1..=self.dimension).filter(|value| !used.contains(value)).collect();
+                rng.shuffle(&mut missing);
+                let mut missing = missing.into_iter();
+                for &index in &box_indices {
+                    if self.squares[index].value == 0 {
+                        let value = missing.next().expect("box has as many blanks as missing values");
+                        self.squares[index].assign(value);
+                    }
+                }
+            }
+        }
+
+        let mut energy = self.conflict_count();
+        let mut temperature = schedule.initial_temperature;
+        let mut stall = 0;
+        let start = Instant::now();
+        let mut iterations = 0;
+        while energy > 0 && iterations < budget.max_iterations && start.elapsed() < budget.max_duration {
+            iterations += 1;
+
+            let group_x = rng.gen_range(boxes_per_row);
+            let group_y = rng.gen_range(boxes_per_column);
+            let movable: Vec<usize> = self.group_indices(group_x * self.box_width, group_y * self.box_height)
+                .into_iter().filter(|&index| !fixed[index]).collect();
+            if movable.len() < 2 {
+                continue;
+            }
+            let a = movable[rng.gen_range(movable.len())];
+            let b = movable[rng.gen_range(movable.len())];
+            if a == b {
+                continue;
+            }
+
+            let delta = self.swap_delta(a, b);
+            if delta <= 0 || rng.next_f64() < (-delta as f64 / temperature).exp() {
+                self.squares.swap(a, b);
+                energy = (energy as isize + delta) as usize;
+                stall = if delta < 0 { 0 } else { stall + 1 };
+            } else {
+                stall += 1;
+            }
+
+            temperature *= schedule.cooling_rate;
+            if stall >= schedule.stall_limit {
+                temperature = schedule.reheat_temperature;
+                stall = 0;
+            }
+        }
+
+        if energy == 0 {
+            Ok(())
+        } else {
+            Err("The annealing search did not converge within its budget")
+        }
+    }
+
+    /// The change in `conflict_count` that swapping `a` and `b` would
+    /// cause, without actually leaving them swapped.
+    fn swap_delta(&mut self, a: usize, b: usize) -> isize {
+        let before = self.local_conflicts(a, b);
+        self.squares.swap(a, b);
+        let after = self.local_conflicts(a, b);
+        self.squares.swap(a, b);
+        after as isize - before as isize
+    }
+
+    /// Duplicate count in just the rows and columns that `a` or `b` sit in.
+    fn local_conflicts(&self, a: usize, b: usize) -> usize {
+        let (row_a, column_a) = (a / self.dimension, a % self.dimension);
+        let (row_b, column_b) = (b / self.dimension, b % self.dimension);
+        let mut units = vec![self.row_indices(row_a), self.column_indices(column_a)];
+        if row_b != row_a {
+            units.push(self.row_indices(row_b));
+        }
+        if column_b != column_a {
+            units.push(self.column_indices(column_b));
+        }
+        units.iter().map(|unit| self.count_duplicates(unit)).sum()
+    }
+
+    /// Total number of duplicate values across every row and column.
+    fn conflict_count(&self) -> usize {
+        (0..self.dimension)
+            .map(|counter| self.count_duplicates(&self.row_indices(counter))
+                          + self.count_duplicates(&self.column_indices(counter)))
+            .sum()
+    }
+
+    fn count_duplicates(&self, indices: &[usize]) -> usize {
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for &index in indices {
+            let value = self.squares[index].value;
+            if value != 0 {
+                *counts.entry(value).or_insert(0) += 1;
+            }
+        }
+        counts.values().map(|&count: &usize| count.saturating_sub(1)).sum()
+    }
+
+    /// Every row, column, and box as a list of square indices.
+    fn all_units(&self) -> Vec<Vec<usize>> {
+        let mut units = Vec::with_capacity(self.dimension * 3);
+        for counter in 0..self.dimension {
+            units.push(self.row_indices(counter));
+            units.push(self.column_indices(counter));
+        }
+        for group_y in 0..self.dimension / self.box_height {
+            for group_x in 0..self.dimension / self.box_width {
+                units.push(self.group_indices(group_x * self.box_width, group_y * self.box_height));
+            }
+        }
+        units
+    }
+}
+
+
+/// How hard a puzzle was to solve with `Puzzle::solve_logic`, ranked by
+/// the most advanced technique that was needed to finish it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    /// Solvable with naked singles alone.
+    Easy,
+    /// Needed at least one hidden single.
+    Medium,
+    /// Needed at least one naked pair/triple elimination.
+    Hard,
+    /// Needed at least one pointing pair / box-line reduction.
+    Expert,
+}
+
+
+/// One step of deduction reported by `Puzzle::next_hint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    /// `technique` determined that `index` must hold `value`.
+    Assign { index: usize, value: usize, technique: &'static str },
+    /// `technique` removed one or more candidates without yet pinning down
+    /// a square's value.
+    Eliminate { technique: &'static str },
+}
+
+
+/// The temperature schedule used by `Puzzle::solve_annealing`.
+#[derive(Debug, Clone, Copy)]
+pub struct AnnealingSchedule {
+    /// Starting temperature; higher accepts more energy-raising moves.
+    pub initial_temperature: f64,
+    /// Multiplier applied to the temperature after every move.
+    pub cooling_rate: f64,
+    /// Temperature to jump back to once the search has stalled.
+    pub reheat_temperature: f64,
+    /// How many moves in a row may fail to lower energy before reheating.
+    pub stall_limit: usize,
+}
+
+impl AnnealingSchedule {
+    pub fn new(initial_temperature: f64, cooling_rate: f64, reheat_temperature: f64, stall_limit: usize) -> AnnealingSchedule {
+        AnnealingSchedule { initial_temperature, cooling_rate, reheat_temperature, stall_limit }
+    }
+}
+
+
+/// How long `Puzzle::solve_annealing` is allowed to search before giving up.
+#[derive(Debug, Clone, Copy)]
+pub struct AnnealingBudget {
+    pub max_iterations: usize,
+    pub max_duration: Duration,
+}
+
+impl AnnealingBudget {
+    pub fn new(max_iterations: usize, max_duration: Duration) -> AnnealingBudget {
+        AnnealingBudget { max_iterations, max_duration }
+    }
+}
+
+
+/// Why `Puzzle::read_from_string`/`read_from_file` failed to parse a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input had no cells in it at all (blank, or only whitespace).
+    Empty,
+    /// The number of cells found wasn't a perfect square, so no square
+    /// grid dimension could be inferred from it.
+    CellCountNotSquare(usize),
+    /// A token in the whitespace-separated format wasn't a valid number.
+    InvalidToken(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "input has no cells to parse"),
+            ParseError::CellCountNotSquare(count) =>
+                write!(f, "found {} cells, which isn't a perfect square", count),
+            ParseError::InvalidToken(token) =>
+                write!(f, "'{}' is not a valid cell value", token),
+        }
+    }
+}
+
+fn is_perfect_square(n: usize) -> bool {
+    let root = (n as f64).sqrt().round() as usize;
+    root * root == n
+}
+
+/// Parses a grid into a flat, row-major list of cell values (`0` meaning
+/// empty), accepting either the compact one-char-per-cell format or
+/// whitespace-separated tokens. See `Puzzle::read_from_string` for the
+/// format details.
+fn parse_cells(source: &str) -> Result<Vec<usize>, ParseError> {
+    let stripped: String = source.chars().filter(|c| !c.is_whitespace()).collect();
+    if stripped.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    // A compact grid only ever uses bare newlines to separate rows; any
+    // other whitespace means cell values are being separated by tokens
+    // instead, which has to be decided before stripping separators away,
+    // since stripped token digits also look like "entirely digits/./_".
+    let without_row_breaks: String =
+        source.chars().filter(|&c| c != '\n' && c != '\r').collect();
+    let is_compact = !without_row_breaks.chars().any(char::is_whitespace)
+        && stripped.chars().all(|c| c.is_ascii_digit() || c == '.' || c == '_');
+
+    if is_compact {
+        if !is_perfect_square(stripped.len()) {
+            return Err(ParseError::CellCountNotSquare(stripped.len()));
+        }
+        return stripped.chars().map(|c| match c {
+            '.' | '_' => Ok(0),
+            digit => Ok(digit.to_digit(10).unwrap() as usize),
+        }).collect();
+    }
+
+    let values: Vec<usize> = source.split_whitespace()
+        .map(|token| match token {
+            "." | "_" => Ok(0),
+            token => token.parse::<usize>().map_err(|_| ParseError::InvalidToken(token.to_string())),
+        })
+        .collect::<Result<_, _>>()?;
+    if !is_perfect_square(values.len()) {
+        return Err(ParseError::CellCountNotSquare(values.len()));
+    }
+    Ok(values)
+}
+
+
+/// Every way to choose `size` items out of `items`, order ignored.
+fn combinations(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    if items.len() < size {
+        return Vec::new();
+    }
+    let mut result = Vec::new();
+    for (position, &item) in items.iter().enumerate() {
+        for mut combo in combinations(&items[position + 1..], size - 1) {
+            combo.insert(0, item);
+            result.push(combo);
+        }
+    }
+    result
 }
 
 
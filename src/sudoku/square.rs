@@ -35,11 +35,32 @@ impl Square {
         self.domain.len()
     }
 
+    /// Returns the set of values this square could still take.
+    pub fn domain(&self) -> &HashSet<usize> {
+        &self.domain
+    }
+
     pub fn set_value_from_domain(&mut self) {
         match self.domain.len() {
             1 => self.value = self.domain.drain().last().unwrap(),
             _ => (),
         }
     }
+
+    /// Assigns `value` directly, clearing the domain since the square is
+    /// no longer in play for deduction.
+    pub fn assign(&mut self, value: usize) {
+        self.value = value;
+        self.domain.clear();
+    }
+
+    /// Picks an arbitrary candidate out of the domain, assigns it, and
+    /// returns it. Used when backtracking needs to guess a value for a
+    /// square whose domain has more than one candidate left.
+    pub fn guess(&mut self) -> Option<usize> {
+        let value = *self.domain.iter().next()?;
+        self.assign(value);
+        Some(value)
+    }
 }
 